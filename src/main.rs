@@ -1,5 +1,5 @@
 use warp::{Filter};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::error::Error;
 use std::fmt::{Display, Debug};
@@ -8,8 +8,15 @@ use serde::export::Formatter;
 use bollard::Docker;
 use std::default::Default;
 use std::sync::Arc;
-use bollard::exec::CreateExecOptions;
-use bollard::container::{CreateContainerOptions, StartContainerOptions, Config, CreateContainerResults, ListContainersOptions, HostConfig};
+use bollard::exec::{CreateExecOptions, StartExecResults};
+use bollard::container::{CreateContainerOptions, StartContainerOptions, Config, CreateContainerResults, ListContainersOptions, HostConfig, PortBinding, RestartPolicy, RestartPolicyNameEnum};
+use bollard::volume::CreateVolumeOptions;
+use std::collections::HashSet;
+use bollard::container::LogsOptions;
+use futures::{Stream, StreamExt};
+use bytes::Bytes;
+use hyper::Body;
+use tokio::sync::Semaphore;
 
 #[derive(Deserialize)]
 struct ContainerStorageConfiguration {
@@ -24,20 +31,162 @@ struct ContainerConfiguration {
     name: String,
     hostname: Option<String>,
     domain: Option<String>,
-    ports: Option<Vec<u16>>,
+    ports: Option<Vec<String>>,
     storage: Option<Vec<ContainerStorageConfiguration>>,
-    environment: Option<HashMap<String, String>>
+    // already-formed bind specs, e.g. compose-style "volume_name:/container/path[:ro]"
+    volumes: Option<Vec<String>>,
+    environment: Option<HashMap<String, String>>,
+    restart: Option<String>,
+    memory: Option<i64>,
+    cpu_shares: Option<i64>,
+    network_mode: Option<String>
 }
 
-#[derive(Debug)]
+#[derive(Deserialize)]
+struct ComposeConfiguration {
+    services: HashMap<String, ServiceConfiguration>,
+    volumes: Option<HashMap<String, ComposeVolumeConfiguration>>
+}
+
+#[derive(Deserialize)]
+struct ServiceConfiguration {
+    image: String,
+    container_name: String,
+    hostname: Option<String>,
+    domain: Option<String>,
+    ports: Option<Vec<String>>,
+    volumes: Option<Vec<String>>,
+    environment: Option<HashMap<String, String>>,
+    depends_on: Option<Vec<String>>,
+    restart: Option<String>,
+    memory: Option<i64>,
+    cpu_shares: Option<i64>,
+    network_mode: Option<String>
+}
+
+impl ServiceConfiguration {
+    /// compose services are started through the same `ContainerConfiguration` path as `/run`
+    fn to_container_configuration(&self) -> ContainerConfiguration {
+        ContainerConfiguration {
+            image: self.image.clone(),
+            name: self.container_name.clone(),
+            hostname: self.hostname.clone(),
+            domain: self.domain.clone(),
+            ports: self.ports.clone(),
+            storage: None,
+            volumes: self.volumes.clone(),
+            environment: self.environment.clone(),
+            restart: self.restart.clone(),
+            memory: self.memory,
+            cpu_shares: self.cpu_shares,
+            network_mode: self.network_mode.clone()
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct ComposeVolumeConfiguration {
+    driver: Option<String>,
+    driver_opts: Option<HashMap<String, String>>
+}
+
+#[derive(Deserialize)]
+struct LogsQuery {
+    tail: Option<u64>
+}
+
+#[derive(Deserialize)]
+struct RunQuery {
+    wait: Option<bool>
+}
+
+#[derive(Serialize)]
+struct RunResult {
+    status: String
+}
+
+#[derive(Deserialize)]
+struct ExecRequest {
+    cmd: Vec<String>,
+    env: Option<HashMap<String, String>>
+}
+
+#[derive(Serialize)]
+struct ExecResult {
+    output: String,
+    exit_code: i64
+}
+
+/// Docker's log stream emits arbitrary byte frames that don't align to lines;
+/// this re-buffers them so each item yielded is a single, whole `\n`-terminated line;
+/// bytes left without a trailing `\n` when the stream ends are yielded as one final item
+fn line_buffered(mut logs: impl Stream<Item = Result<bollard::container::LogOutput, bollard::errors::Error>> + Unpin + Send + 'static)
+    -> impl Stream<Item = Result<Bytes, bollard::errors::Error>> + Send + 'static {
+    futures::stream::unfold((logs, Vec::<u8>::new(), false), move |(mut logs, mut buf, mut done)| async move {
+        loop {
+            if let Some(pos) = buf.iter().position(|b| *b == b'\n') {
+                let line: Vec<u8> = buf.drain(..=pos).collect();
+                return Some((Ok(Bytes::from(line)), (logs, buf, done)));
+            }
+            if done {
+                return if buf.is_empty() {
+                    None
+                } else {
+                    let line = std::mem::take(&mut buf);
+                    Some((Ok(Bytes::from(line)), (logs, buf, done)))
+                };
+            }
+            match logs.next().await {
+                Some(Ok(chunk)) => buf.extend_from_slice(&chunk.into_bytes()),
+                Some(Err(e)) => return Some((Err(e), (logs, buf, done))),
+                None => done = true
+            }
+        }
+    })
+}
+
+#[derive(Debug, Clone, Serialize)]
 enum StartFailureReason {
     StoragePathDNE,
     ImageDNE,
     PortBindFailure,
     PermissionDenied,
+    DependencyCycle(Vec<String>),
+    ContainerNotFound,
+    InvalidConfiguration,
     Other
 }
 
+#[derive(Serialize)]
+struct ErrorResponse {
+    error: String,
+    reason: StartFailureReason
+}
+
+fn status_for_reason(reason: &StartFailureReason) -> warp::http::StatusCode {
+    match reason {
+        StartFailureReason::PermissionDenied => warp::http::StatusCode::FORBIDDEN,
+        StartFailureReason::ImageDNE => warp::http::StatusCode::NOT_FOUND,
+        StartFailureReason::StoragePathDNE => warp::http::StatusCode::BAD_REQUEST,
+        StartFailureReason::PortBindFailure => warp::http::StatusCode::CONFLICT,
+        StartFailureReason::ContainerNotFound => warp::http::StatusCode::NOT_FOUND,
+        StartFailureReason::DependencyCycle(_) => warp::http::StatusCode::BAD_REQUEST,
+        StartFailureReason::InvalidConfiguration => warp::http::StatusCode::BAD_REQUEST,
+        StartFailureReason::Other => warp::http::StatusCode::INTERNAL_SERVER_ERROR
+    }
+}
+
+async fn handle_rejection(err: warp::Rejection) -> Result<impl warp::Reply, std::convert::Infallible> {
+    let (status, message) = if let Some(e) = err.find::<ContainerRunError>() {
+        (status_for_reason(&e.reason), ErrorResponse { error: e.message.clone(), reason: e.reason.clone() })
+    } else if err.is_not_found() {
+        (warp::http::StatusCode::NOT_FOUND, ErrorResponse { error: "not found".to_owned(), reason: StartFailureReason::Other })
+    } else {
+        (warp::http::StatusCode::INTERNAL_SERVER_ERROR, ErrorResponse { error: "internal error".to_owned(), reason: StartFailureReason::Other })
+    };
+    Ok(warp::reply::with_status(warp::reply::json(&message), status))
+}
+
 #[derive(Debug)]
 struct ContainerRunError {
     message: String,
@@ -82,32 +231,177 @@ impl Display for ContainerRunError {
     }
 }
 
+/// parse a compose-style port spec (`"HOST:CONTAINER"` or `"HOST:CONTAINER/udp"`)
+/// into an exposed-ports key and a host port binding
+fn parse_port_spec(spec: &str) -> Result<(String, PortBinding), ContainerRunError> {
+    let (ports, protocol) = match spec.find('/') {
+        Some(idx) => (&spec[..idx], &spec[idx + 1..]),
+        None => (spec, "tcp")
+    };
+    let mut parts = ports.splitn(2, ':');
+    let (host_port, container_port) = match (parts.next(), parts.next()) {
+        (Some(host), Some(container)) => (host, container),
+        _ => return Err(ContainerRunError {
+            message: format!("invalid port spec '{}', expected HOST:CONTAINER", spec),
+            reason: StartFailureReason::PortBindFailure
+        })
+    };
+    Ok((
+        format!("{}/{}", container_port, protocol),
+        PortBinding { host_ip: Some("0.0.0.0".to_owned()), host_port: Some(host_port.to_owned()) }
+    ))
+}
+
+fn build_port_config(ports: &[String]) -> Result<(HashMap<String, HashMap<(), ()>>, HashMap<String, Option<Vec<PortBinding>>>), ContainerRunError> {
+    let mut exposed_ports = HashMap::new();
+    let mut port_bindings = HashMap::new();
+    for spec in ports {
+        let (key, binding) = parse_port_spec(spec)?;
+        exposed_ports.insert(key.clone(), HashMap::new());
+        port_bindings.insert(key, Some(vec![binding]));
+    }
+    Ok((exposed_ports, port_bindings))
+}
+
+/// map a compose-style `restart` string (`no`/`on-failure`/`always`/`unless-stopped`) to a `RestartPolicy`
+fn restart_policy_from_str(restart: &str) -> Result<RestartPolicy, ContainerRunError> {
+    let name = match restart {
+        "no" => RestartPolicyNameEnum::NO,
+        "always" => RestartPolicyNameEnum::ALWAYS,
+        "unless-stopped" => RestartPolicyNameEnum::UNLESS_STOPPED,
+        "on-failure" => RestartPolicyNameEnum::ON_FAILURE,
+        _ => return Err(ContainerRunError {
+            message: format!("invalid restart policy '{}', expected one of: no, on-failure, always, unless-stopped", restart),
+            reason: StartFailureReason::InvalidConfiguration
+        })
+    };
+    Ok(RestartPolicy { name: Some(name), maximum_retry_count: None })
+}
+
+/// how to reach the Docker daemon, selected from the standard `DOCKER_*` env vars
+enum DaemonTransport {
+    Local,
+    Unix(String),
+    Http(String),
+    Ssl { host: String, cert_path: String, key_path: String, ca_path: String }
+}
+
+struct DaemonConfig {
+    transport: DaemonTransport
+}
+
+impl DaemonConfig {
+    fn from_env() -> Self {
+        let transport = match std::env::var("DOCKER_HOST") {
+            Ok(host) if host.starts_with("unix://") => {
+                DaemonTransport::Unix(host.trim_start_matches("unix://").to_owned())
+            },
+            Ok(host) if std::env::var("DOCKER_TLS_VERIFY").map(|v| v == "1").unwrap_or(false) => {
+                let cert_dir = std::env::var("DOCKER_CERT_PATH").unwrap_or_else(|_| ".".to_owned());
+                DaemonTransport::Ssl {
+                    host,
+                    cert_path: format!("{}/cert.pem", cert_dir),
+                    key_path: format!("{}/key.pem", cert_dir),
+                    ca_path: format!("{}/ca.pem", cert_dir)
+                }
+            },
+            Ok(host) => DaemonTransport::Http(host),
+            Err(_) => DaemonTransport::Local
+        };
+        DaemonConfig { transport }
+    }
+}
+
+fn connect_docker(config: DaemonConfig) -> Result<Docker, bollard::errors::Error> {
+    match config.transport {
+        DaemonTransport::Local => Docker::connect_with_local_defaults(),
+        DaemonTransport::Unix(socket_path) => Docker::connect_with_unix(&socket_path, 120, bollard::API_DEFAULT_VERSION),
+        DaemonTransport::Http(host) => Docker::connect_with_http(&host, 120, bollard::API_DEFAULT_VERSION),
+        DaemonTransport::Ssl { host, cert_path, key_path, ca_path } => Docker::connect_with_ssl(
+            &host,
+            std::path::Path::new(&key_path),
+            std::path::Path::new(&cert_path),
+            std::path::Path::new(&ca_path),
+            120,
+            bollard::API_DEFAULT_VERSION
+        )
+    }
+}
+
 #[tokio::main]
 async fn main() {
     let container_platform = "docker";
     let runner = match container_platform {
         "docker" => {
-            let docker = Docker::connect_with_local_defaults().unwrap();
+            let docker = connect_docker(DaemonConfig::from_env()).unwrap();
             DockerRunner::new(docker)
         }
         _ => {
             unreachable!();
         }
     };
+    let num_max_jobs: usize = std::env::var("NUM_MAX_JOBS").ok().and_then(|v| v.parse().ok()).unwrap_or(4);
+    let scheduler = JobScheduler::new(runner.clone(), num_max_jobs);
     let post = warp::post();
     let run = warp::path!("run").
+        and(warp::query::<RunQuery>()).
         and(warp::body::json()).
-        and(with_runner(runner.clone())).
-        and_then( |conf: ContainerConfiguration, runner: DockerRunner| async move {
-            // TODO JSON format and status code on errors
-            match runner.run_container(conf).await {
+        and(with_scheduler(scheduler.clone())).
+        and_then( |query: RunQuery, conf: ContainerConfiguration, scheduler: JobScheduler| async move {
+            if query.wait.unwrap_or(true) {
+                match scheduler.run_container(conf).await {
+                    Ok(()) => Ok(warp::reply::with_status(warp::reply::json(&RunResult{status: "started".to_owned()}), warp::http::StatusCode::OK)),
+                    Err(error) => Err(warp::reject::custom(error))
+                }
+            } else {
+                let name = conf.name.clone();
+                tokio::spawn(async move {
+                    if let Err(error) = scheduler.run_container(conf).await {
+                        eprintln!("queued container '{}' failed to start: {}", name, error.message);
+                    }
+                });
+                Ok(warp::reply::with_status(warp::reply::json(&RunResult{status: "queued".to_owned()}), warp::http::StatusCode::ACCEPTED))
+            }
+        });
+    let compose_up = warp::path!("compose" / "up").
+        and(warp::body::json()).
+        and(with_scheduler(scheduler.clone())).
+        and_then( |conf: ComposeConfiguration, scheduler: JobScheduler| async move {
+            match scheduler.run_compose(conf).await {
                 Ok(()) => Ok(format!("Started successfully")),
                 Err(error) => {
                     Err(warp::reject::custom(error))
                 }
             }
         });
-    warp::serve(post.and(run)).run(([127, 0, 0, 1], 3030)).await;
+    let exec_route = warp::path!("exec" / String).
+        and(warp::body::json()).
+        and(with_scheduler(scheduler.clone())).
+        and_then( |name: String, req: ExecRequest, scheduler: JobScheduler| async move {
+            match scheduler.exec(&name, req).await {
+                Ok(result) => Ok(warp::reply::json(&result)),
+                Err(error) => Err(warp::reject::custom(error))
+            }
+        });
+    let logs = warp::get().
+        and(warp::path!("logs" / String)).
+        and(warp::query::<LogsQuery>()).
+        and(with_runner(runner.clone())).
+        and_then(|name: String, query: LogsQuery, runner: DockerRunner| async move {
+            match runner.find_container(&name).await {
+                Ok(Some(_)) => {
+                    let stream = runner.stream_logs(&name, query.tail);
+                    Ok(warp::reply::Response::new(Body::wrap_stream(stream)))
+                },
+                Ok(None) => Err(warp::reject::custom(ContainerRunError {
+                    message: format!("no container named '{}'", name),
+                    reason: StartFailureReason::ContainerNotFound
+                })),
+                Err(e) => Err(warp::reject::custom(ContainerRunError::from(e)))
+            }
+        });
+    let routes = post.and(run.or(compose_up).or(exec_route)).or(logs).recover(handle_rejection);
+    warp::serve(routes).run(([127, 0, 0, 1], 3030)).await;
 }
 
 #[derive(Clone, Debug)]
@@ -120,6 +414,41 @@ fn with_runner(runner: DockerRunner) -> impl Filter<Extract = (DockerRunner,), E
     warp::any().map(move || runner.clone())
 }
 
+/**
+caps the number of simultaneous create/start operations against the daemon so a burst
+of requests can't fan out unbounded async work; callers queue on the semaphore until a permit frees up
+*/
+#[derive(Clone)]
+struct JobScheduler {
+    runner: DockerRunner,
+    permits: Arc<Semaphore>
+}
+
+impl JobScheduler {
+    fn new(runner: DockerRunner, num_max_jobs: usize) -> Self {
+        JobScheduler { runner, permits: Arc::new(Semaphore::new(num_max_jobs)) }
+    }
+
+    async fn run_container(&self, conf: ContainerConfiguration) -> Result<(), ContainerRunError> {
+        let _permit = self.permits.acquire().await;
+        self.runner.run_container(conf).await
+    }
+
+    async fn run_compose(&self, conf: ComposeConfiguration) -> Result<(), ContainerRunError> {
+        let _permit = self.permits.acquire().await;
+        self.runner.run_compose(conf).await
+    }
+
+    async fn exec(&self, name: &str, req: ExecRequest) -> Result<ExecResult, ContainerRunError> {
+        let _permit = self.permits.acquire().await;
+        self.runner.exec(name, req).await
+    }
+}
+
+fn with_scheduler(scheduler: JobScheduler) -> impl Filter<Extract = (JobScheduler,), Error = std::convert::Infallible> + Clone {
+    warp::any().map(move || scheduler.clone())
+}
+
 
 impl DockerRunner {
     fn new(docker: Docker) -> Self {
@@ -128,15 +457,15 @@ impl DockerRunner {
         }
     }
 
-    async fn find_container(&self, conf: &ContainerConfiguration) -> Result<Option<String>, bollard::errors::Error> {
+    async fn find_container(&self, name: &str) -> Result<Option<String>, bollard::errors::Error> {
         let result = self.docker.list_containers(Some(ListContainersOptions::<String>{all: true, ..Default::default()})).await;
         match result {
             Ok(list_results) => {
-                let mut name = "/".to_owned();
-                name.push_str(&conf.name);
+                let mut full_name = "/".to_owned();
+                full_name.push_str(name);
                 match list_results.into_iter().filter(|result| {
                     // TODO what do we do if the name exists but it's for a different image?
-                    result.names.contains(&name)
+                    result.names.contains(&full_name)
                 }).nth(0) {
                     Some(image) => Ok(Some(image.id)),
                     None => Ok(None)
@@ -148,23 +477,38 @@ impl DockerRunner {
         }
     }
 
-    async fn create_container(&self, conf: &ContainerConfiguration) -> Result<CreateContainerResults, bollard::errors::Error> {
+    async fn create_container(&self, conf: &ContainerConfiguration) -> Result<CreateContainerResults, ContainerRunError> {
         let create_opts = Some(CreateContainerOptions{
             name: conf.name.to_owned()
         });
-        let host_config = if let Some(storage) = &conf.storage {
-            Some(HostConfig{
-                binds: Some(storage.into_iter().fold(Vec::new(), | mut v, vol| {
-                    let mut host_path = vol.host.clone();
-                    let local_path = vol.local.clone();
-                    host_path.push_str(&local_path);
-                    if vol.ro { host_path.push_str(":ro")};
-                    v.push(host_path);
-                    v
-                })),
-                ..Default::default()
-            })
-        } else {None};
+        let (exposed_ports, port_bindings) = match &conf.ports {
+            Some(ports) => {
+                let (exposed_ports, port_bindings) = build_port_config(ports)?;
+                (Some(exposed_ports), Some(port_bindings))
+            },
+            None => (None, None)
+        };
+        let mut binds: Vec<String> = conf.storage.as_ref().map(|storage| storage.into_iter().fold(Vec::new(), | mut v, vol| {
+            let mut host_path = vol.host.clone();
+            let local_path = vol.local.clone();
+            host_path.push_str(&local_path);
+            if vol.ro { host_path.push_str(":ro")};
+            v.push(host_path);
+            v
+        })).unwrap_or_default();
+        if let Some(volumes) = &conf.volumes {
+            binds.extend(volumes.clone());
+        }
+        let binds = if binds.is_empty() { None } else { Some(binds) };
+        let host_config = Some(HostConfig{
+            binds,
+            port_bindings,
+            restart_policy: conf.restart.as_deref().map(restart_policy_from_str).transpose()?,
+            memory: conf.memory,
+            cpu_shares: conf.cpu_shares,
+            network_mode: conf.network_mode.clone(),
+            ..Default::default()
+        });
         let create_config = Config{
             image: Some(conf.image.to_owned()),
             env: match &conf.environment {
@@ -179,17 +523,18 @@ impl DockerRunner {
             },
             domainname: conf.domain.clone(),
             hostname: conf.hostname.clone(),
+            exposed_ports,
             host_config,
             ..Default::default()
         };
-        self.docker.create_container(create_opts, create_config).await
+        Ok(self.docker.create_container(create_opts, create_config).await?)
     }
 
     /**
     create a container if one does not exist, and then start it, based on provided configuration
     */
     async fn run_container(&self, conf: ContainerConfiguration) -> Result<(), ContainerRunError> {
-        let found = self.find_container(&conf).await?;
+        let found = self.find_container(&conf.name).await?;
         // TODO if found and running, probably raise an error
         // TODO if found and not running, may need to update config (env, etc)
         let container_id = match found {
@@ -198,4 +543,98 @@ impl DockerRunner {
         };
         Ok(self.docker.start_container(&container_id, None::<StartContainerOptions<String>>).await?)
     }
+
+    async fn create_volume(&self, name: &str, conf: &ComposeVolumeConfiguration) -> Result<(), bollard::errors::Error> {
+        self.docker.create_volume(CreateVolumeOptions {
+            name: name.to_owned(),
+            driver: conf.driver.clone().unwrap_or_default(),
+            driver_opts: conf.driver_opts.clone().unwrap_or_default(),
+            ..Default::default()
+        }).await?;
+        Ok(())
+    }
+
+    /**
+    bring up a whole docker-compose style stack: create named volumes, then start
+    services in dependency order, rejecting if `depends_on` forms a cycle
+    */
+    async fn run_compose(&self, conf: ComposeConfiguration) -> Result<(), ContainerRunError> {
+        if let Some(volumes) = &conf.volumes {
+            for (name, vol_conf) in volumes.iter() {
+                self.create_volume(name, vol_conf).await?;
+            }
+        }
+
+        let mut remaining_deps: HashMap<String, HashSet<String>> = conf.services.iter().map(|(name, svc)| {
+            (name.clone(), svc.depends_on.clone().unwrap_or_default().into_iter().collect())
+        }).collect();
+
+        while !remaining_deps.is_empty() {
+            let ready: Vec<String> = remaining_deps.iter()
+                .filter(|(_, deps)| deps.is_empty())
+                .map(|(name, _)| name.clone())
+                .collect();
+            if ready.is_empty() {
+                let stuck: Vec<String> = remaining_deps.keys().cloned().collect();
+                return Err(ContainerRunError {
+                    message: format!("dependency cycle among services: {}", stuck.join(", ")),
+                    reason: StartFailureReason::DependencyCycle(stuck)
+                });
+            }
+            for name in &ready {
+                self.run_container(conf.services[name].to_container_configuration()).await?;
+                remaining_deps.remove(name);
+            }
+            for deps in remaining_deps.values_mut() {
+                for name in &ready {
+                    deps.remove(name);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /**
+    tail and follow a container's combined stdout/stderr, re-buffered into whole lines
+    */
+    fn stream_logs(&self, name: &str, tail: Option<u64>) -> impl Stream<Item = Result<Bytes, bollard::errors::Error>> + Send + 'static {
+        let options = LogsOptions::<String> {
+            follow: true,
+            stdout: true,
+            stderr: true,
+            tail: tail.map(|n| n.to_string()).unwrap_or_else(|| "all".to_owned()),
+            ..Default::default()
+        };
+        line_buffered(self.docker.logs(name, Some(options)))
+    }
+
+    /**
+    run a one-off command inside an already-running container, returning its combined
+    stdout/stderr output and exit code
+    */
+    async fn exec(&self, name: &str, req: ExecRequest) -> Result<ExecResult, ContainerRunError> {
+        let container_id = self.find_container(name).await?.ok_or_else(|| ContainerRunError {
+            message: format!("no container named '{}'", name),
+            reason: StartFailureReason::ContainerNotFound
+        })?;
+        let env = req.env.map(|hm| hm.iter().map(|(k, v)| format!("{}={}", k, v)).collect());
+        let exec = self.docker.create_exec(&container_id, CreateExecOptions {
+            cmd: Some(req.cmd),
+            env,
+            attach_stdout: Some(true),
+            attach_stderr: Some(true),
+            ..Default::default()
+        }).await?;
+        // accumulate raw bytes first; exec frames don't align to UTF-8 char boundaries,
+        // so decoding per-chunk can split (and corrupt) a multi-byte character
+        let mut raw_output: Vec<u8> = Vec::new();
+        if let StartExecResults::Attached { output: mut stream, .. } = self.docker.start_exec(&exec.id, None).await? {
+            while let Some(Ok(chunk)) = stream.next().await {
+                raw_output.extend_from_slice(&chunk.into_bytes());
+            }
+        }
+        let output = String::from_utf8_lossy(&raw_output).into_owned();
+        let inspect = self.docker.inspect_exec(&exec.id).await?;
+        Ok(ExecResult { output, exit_code: inspect.exit_code.unwrap_or_default() })
+    }
 }